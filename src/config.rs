@@ -0,0 +1,65 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+/// An action to take on the underlying issue when a project field matches a
+/// rule in the status-action config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "action")]
+pub enum Action {
+    Close,
+    Reopen,
+    AddLabel { label: String },
+    AssignReviewer { reviewer: String },
+}
+
+/// Maps a project "Status" field value (e.g. "Done", "Blocked") to the
+/// `Action` to take on the issue it's attached to.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    rules: HashMap<String, Action>,
+}
+
+/// Why a status-action config failed to load, so the caller can fail fast at
+/// boot with a clear message instead of panicking per-request.
+#[derive(Debug)]
+pub enum ConfigError {
+    MissingEnvVar(String),
+    Io(std::io::Error),
+    Parse(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingEnvVar(name) => write!(f, "{} must be set", name),
+            ConfigError::Io(e) => write!(f, "failed to read config file: {}", e),
+            ConfigError::Parse(e) => write!(f, "invalid config: {}", e),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the status-action mapping from the path in `STATUS_ACTIONS_CONFIG`.
+    /// The file is parsed as TOML if its extension is `.toml`, JSON otherwise.
+    pub fn load_from_env() -> Result<Config, ConfigError> {
+        let path = std::env::var("STATUS_ACTIONS_CONFIG")
+            .map_err(|_| ConfigError::MissingEnvVar("STATUS_ACTIONS_CONFIG".to_string()))?;
+        Config::load(&path)
+    }
+
+    pub fn load(path: &str) -> Result<Config, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+
+        if path.ends_with(".toml") {
+            toml::from_str(&contents).map_err(|e| ConfigError::Parse(e.to_string()))
+        } else {
+            serde_json::from_str(&contents).map_err(|e| ConfigError::Parse(e.to_string()))
+        }
+    }
+
+    pub fn action_for(&self, status: &str) -> Option<&Action> {
+        self.rules.get(status)
+    }
+}
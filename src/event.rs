@@ -0,0 +1,242 @@
+use serde_json::Value;
+use std::fmt;
+
+/// A parsed GitHub webhook payload. Unrecognized event shapes fall back to
+/// `Other` rather than being treated as errors — we only care about
+/// `projects_v2_item` events.
+#[derive(Debug)]
+pub enum GithubEvent {
+    ProjectsV2Item {
+        action: ProjectsV2ItemAction,
+        node_id: String,
+        changed_field: Option<String>,
+    },
+    Other,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProjectsV2ItemAction {
+    Reordered,
+    Edited,
+    Archived,
+    Other(String),
+}
+
+/// Records exactly which part of the payload was missing or the wrong shape,
+/// so a schema change is diagnosable from the log line instead of silently
+/// degrading to "not found".
+#[derive(Debug)]
+pub enum GithubHookError {
+    MissingElement { path: String },
+    BadType { path: String, expected: String },
+}
+
+impl fmt::Display for GithubHookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GithubHookError::MissingElement { path } => {
+                write!(f, "missing element at {}", path)
+            }
+            GithubHookError::BadType { path, expected } => {
+                write!(f, "expected {} at {}", expected, path)
+            }
+        }
+    }
+}
+
+impl GithubEvent {
+    pub fn parse(payload: &Value) -> Result<GithubEvent, GithubHookError> {
+        let Some(item) = payload.get("projects_v2_item") else {
+            return Ok(GithubEvent::Other);
+        };
+
+        let action = payload
+            .get("action")
+            .ok_or_else(|| GithubHookError::MissingElement {
+                path: "action".to_string(),
+            })?
+            .as_str()
+            .ok_or_else(|| GithubHookError::BadType {
+                path: "action".to_string(),
+                expected: "string".to_string(),
+            })?;
+
+        let node_id = item
+            .get("node_id")
+            .ok_or_else(|| GithubHookError::MissingElement {
+                path: "projects_v2_item.node_id".to_string(),
+            })?
+            .as_str()
+            .ok_or_else(|| GithubHookError::BadType {
+                path: "projects_v2_item.node_id".to_string(),
+                expected: "string".to_string(),
+            })?
+            .to_string();
+
+        let action = match action {
+            "reordered" => ProjectsV2ItemAction::Reordered,
+            "edited" => ProjectsV2ItemAction::Edited,
+            "archived" => ProjectsV2ItemAction::Archived,
+            other => ProjectsV2ItemAction::Other(other.to_string()),
+        };
+
+        // Only `edited` events carry a `changes` object, and only when GitHub
+        // knows which field moved; reordering and archiving never set it.
+        let changed_field = payload
+            .get("changes")
+            .and_then(|changes| changes.get("field_value"))
+            .and_then(|field_value| field_value.get("field_name"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        Ok(GithubEvent::ProjectsV2Item {
+            action,
+            node_id,
+            changed_field,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_reordered() {
+        let payload = json!({
+            "action": "reordered",
+            "projects_v2_item": { "node_id": "PVTI_123" },
+        });
+
+        let event = GithubEvent::parse(&payload).unwrap();
+
+        match event {
+            GithubEvent::ProjectsV2Item {
+                action,
+                node_id,
+                changed_field,
+            } => {
+                assert_eq!(action, ProjectsV2ItemAction::Reordered);
+                assert_eq!(node_id, "PVTI_123");
+                assert_eq!(changed_field, None);
+            }
+            GithubEvent::Other => panic!("expected a ProjectsV2Item event"),
+        }
+    }
+
+    #[test]
+    fn parses_edited_and_archived() {
+        for (action_str, expected) in [
+            ("edited", ProjectsV2ItemAction::Edited),
+            ("archived", ProjectsV2ItemAction::Archived),
+        ] {
+            let payload = json!({
+                "action": action_str,
+                "projects_v2_item": { "node_id": "PVTI_123" },
+            });
+
+            match GithubEvent::parse(&payload).unwrap() {
+                GithubEvent::ProjectsV2Item { action, .. } => assert_eq!(action, expected),
+                GithubEvent::Other => panic!("expected a ProjectsV2Item event"),
+            }
+        }
+    }
+
+    #[test]
+    fn edited_event_records_the_changed_field_name() {
+        let payload = json!({
+            "action": "edited",
+            "projects_v2_item": { "node_id": "PVTI_123" },
+            "changes": { "field_value": { "field_name": "Status" } },
+        });
+
+        match GithubEvent::parse(&payload).unwrap() {
+            GithubEvent::ProjectsV2Item { changed_field, .. } => {
+                assert_eq!(changed_field, Some("Status".to_string()));
+            }
+            GithubEvent::Other => panic!("expected a ProjectsV2Item event"),
+        }
+    }
+
+    #[test]
+    fn reordered_event_has_no_changed_field() {
+        let payload = json!({
+            "action": "reordered",
+            "projects_v2_item": { "node_id": "PVTI_123" },
+        });
+
+        match GithubEvent::parse(&payload).unwrap() {
+            GithubEvent::ProjectsV2Item { changed_field, .. } => assert_eq!(changed_field, None),
+            GithubEvent::Other => panic!("expected a ProjectsV2Item event"),
+        }
+    }
+
+    #[test]
+    fn parses_unknown_action_as_other_variant() {
+        let payload = json!({
+            "action": "converted",
+            "projects_v2_item": { "node_id": "PVTI_123" },
+        });
+
+        match GithubEvent::parse(&payload).unwrap() {
+            GithubEvent::ProjectsV2Item { action, .. } => {
+                assert_eq!(action, ProjectsV2ItemAction::Other("converted".to_string()));
+            }
+            GithubEvent::Other => panic!("expected a ProjectsV2Item event"),
+        }
+    }
+
+    #[test]
+    fn non_project_event_without_action_is_other() {
+        let payload = json!({ "ref": "refs/heads/main" });
+
+        assert!(matches!(
+            GithubEvent::parse(&payload).unwrap(),
+            GithubEvent::Other
+        ));
+    }
+
+    #[test]
+    fn missing_action_on_a_project_event_is_an_error() {
+        let payload = json!({
+            "projects_v2_item": { "node_id": "PVTI_123" },
+        });
+
+        match GithubEvent::parse(&payload) {
+            Err(GithubHookError::MissingElement { path }) => assert_eq!(path, "action"),
+            other => panic!("expected MissingElement(\"action\"), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn non_string_action_is_a_bad_type_error() {
+        let payload = json!({
+            "action": 1,
+            "projects_v2_item": { "node_id": "PVTI_123" },
+        });
+
+        match GithubEvent::parse(&payload) {
+            Err(GithubHookError::BadType { path, expected }) => {
+                assert_eq!(path, "action");
+                assert_eq!(expected, "string");
+            }
+            other => panic!("expected BadType(\"action\"), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_node_id_is_an_error() {
+        let payload = json!({
+            "action": "reordered",
+            "projects_v2_item": {},
+        });
+
+        match GithubEvent::parse(&payload) {
+            Err(GithubHookError::MissingElement { path }) => {
+                assert_eq!(path, "projects_v2_item.node_id")
+            }
+            other => panic!("expected MissingElement(\"projects_v2_item.node_id\"), got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use atom_syndication::{ContentBuilder, EntryBuilder, FeedBuilder};
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{FromRow, SqlitePool};
+use std::env;
+use std::str::FromStr;
+
+const CREATE_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS card_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    repo_owner TEXT NOT NULL,
+    repo_name TEXT NOT NULL,
+    issue_number INTEGER NOT NULL,
+    issue_title TEXT NOT NULL,
+    status TEXT NOT NULL,
+    occurred_at TEXT NOT NULL
+)
+"#;
+
+// GitHub re-emits `edited` on every card field change and `reordered` on
+// every drag within a column, neither of which implies the Status field
+// actually transitioned. This table remembers the last status observed per
+// project item (whether or not a rule matched it) so `handle_webhook` can
+// tell a real transition from a re-delivered no-op.
+const CREATE_STATE_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS card_item_state (
+    node_id TEXT PRIMARY KEY,
+    last_status TEXT NOT NULL
+)
+"#;
+
+/// A card event as it comes out of the action dispatcher, before it has an
+/// id or a persisted timestamp.
+pub struct NewCardEvent {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub issue_number: i64,
+    pub issue_title: String,
+    pub status: String,
+}
+
+#[derive(Debug, FromRow)]
+struct CardEvent {
+    id: i64,
+    repo_owner: String,
+    repo_name: String,
+    issue_number: i64,
+    issue_title: String,
+    status: String,
+    occurred_at: DateTime<Utc>,
+}
+
+/// Connects to the SQLite database in `DATABASE_URL` (defaulting to a local
+/// file) and makes sure the `card_events` table exists.
+pub async fn init_pool() -> SqlitePool {
+    let database_url =
+        env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://card_events.db".to_string());
+
+    let connect_options = SqliteConnectOptions::from_str(&database_url)
+        .expect("invalid DATABASE_URL")
+        .create_if_missing(true);
+
+    let pool = SqlitePoolOptions::new()
+        .connect_with(connect_options)
+        .await
+        .expect("failed to connect to the card-events database");
+
+    sqlx::query(CREATE_TABLE_SQL)
+        .execute(&pool)
+        .await
+        .expect("failed to create the card_events table");
+
+    sqlx::query(CREATE_STATE_TABLE_SQL)
+        .execute(&pool)
+        .await
+        .expect("failed to create the card_item_state table");
+
+    pool
+}
+
+/// The status last observed for a project item, if any. Used to tell a
+/// re-delivered `reordered`/`edited` webhook apart from a real Status
+/// transition.
+pub async fn last_known_status(pool: &SqlitePool, node_id: &str) -> Option<String> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT last_status FROM card_item_state WHERE node_id = ?",
+    )
+    .bind(node_id)
+    .fetch_optional(pool)
+    .await
+    .unwrap_or_else(|e| {
+        error!("Failed to load last known status for {}: {}", node_id, e);
+        None
+    })
+}
+
+/// Records the status just observed for a project item, so the next
+/// webhook for the same item can be checked against it.
+pub async fn set_known_status(pool: &SqlitePool, node_id: &str, status: &str) {
+    let result = sqlx::query(
+        "INSERT INTO card_item_state (node_id, last_status) VALUES (?, ?) \
+         ON CONFLICT(node_id) DO UPDATE SET last_status = excluded.last_status",
+    )
+    .bind(node_id)
+    .bind(status)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        error!("Failed to persist last known status for {}: {}", node_id, e);
+    }
+}
+
+/// The status recorded by the most recent feed entry for an issue, if any.
+/// Lets `record_event` refuse a duplicate even if a caller forgets to check
+/// first, so the feed can't fill up with repeated entries for the same
+/// no-op re-delivery.
+async fn most_recent_status(
+    pool: &SqlitePool,
+    repo_owner: &str,
+    repo_name: &str,
+    issue_number: i64,
+) -> Option<String> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT status FROM card_events \
+         WHERE repo_owner = ? AND repo_name = ? AND issue_number = ? \
+         ORDER BY id DESC LIMIT 1",
+    )
+    .bind(repo_owner)
+    .bind(repo_name)
+    .bind(issue_number)
+    .fetch_optional(pool)
+    .await
+    .unwrap_or_else(|e| {
+        error!("Failed to load most recent feed status for {}/{} #{}: {}", repo_owner, repo_name, issue_number, e);
+        None
+    })
+}
+
+/// Records a processed card movement so it shows up in the Atom feed. A
+/// no-op: skips the insert if the last entry for this issue already has the
+/// same status, so a re-delivered webhook can't pad the feed with repeated
+/// entries for the same transition.
+pub async fn record_event(pool: &SqlitePool, event: &NewCardEvent) {
+    let previous =
+        most_recent_status(pool, &event.repo_owner, &event.repo_name, event.issue_number).await;
+
+    if previous.as_deref() == Some(event.status.as_str()) {
+        info!(
+            "Skipping duplicate feed entry for {}/{} #{}: status {:?} unchanged.",
+            event.repo_owner, event.repo_name, event.issue_number, event.status
+        );
+        return;
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO card_events (repo_owner, repo_name, issue_number, issue_title, status, occurred_at) \
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&event.repo_owner)
+    .bind(&event.repo_name)
+    .bind(event.issue_number)
+    .bind(&event.issue_title)
+    .bind(&event.status)
+    .bind(Utc::now())
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        error!("Failed to persist card event: {}", e);
+    }
+}
+
+async fn recent_events(pool: &SqlitePool, limit: i64) -> Vec<CardEvent> {
+    sqlx::query_as::<_, CardEvent>(
+        "SELECT id, repo_owner, repo_name, issue_number, issue_title, status, occurred_at \
+         FROM card_events ORDER BY id DESC LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_else(|e| {
+        error!("Failed to load recent card events: {}", e);
+        Vec::new()
+    })
+}
+
+/// Renders the most recent processed card movements as an Atom feed.
+pub async fn render_feed(pool: &SqlitePool) -> String {
+    let events = recent_events(pool, 50).await;
+
+    let entries = events
+        .iter()
+        .map(|event| {
+            EntryBuilder::default()
+                .title(format!(
+                    "{}/{} #{}: {}",
+                    event.repo_owner, event.repo_name, event.issue_number, event.issue_title
+                ))
+                .id(format!("urn:card-mover:event:{}", event.id))
+                .updated(event.occurred_at.fixed_offset())
+                .content(Some(
+                    ContentBuilder::default()
+                        .value(Some(format!(
+                            "Status \"{}\" triggered automation on {}/{} #{}.",
+                            event.status, event.repo_owner, event.repo_name, event.issue_number
+                        )))
+                        .build(),
+                ))
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    let updated = events
+        .first()
+        .map(|event| event.occurred_at.fixed_offset())
+        .unwrap_or_else(|| Utc::now().fixed_offset());
+
+    let feed = FeedBuilder::default()
+        .title("Project Card Mover activity")
+        .id("urn:card-mover:feed")
+        .updated(updated)
+        .entries(entries)
+        .build();
+
+    feed.to_string()
+}
@@ -3,110 +3,268 @@ extern crate log;
 extern crate reqwest;
 extern crate serde_json;
 
+mod config;
+mod event;
+mod feed;
+
+use config::{Action, Config};
+use event::{GithubEvent, ProjectsV2ItemAction};
+use feed::NewCardEvent;
+use hmac::{Hmac, Mac};
 use octocrab::models;
 use serde_json::Value;
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::env;
+use std::sync::Arc;
+use warp::http::StatusCode;
 use warp::Filter;
 
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug)]
+struct InvalidSignature;
+
+impl warp::reject::Reject for InvalidSignature {}
+
+#[derive(Debug)]
+struct InvalidPayload;
+
+impl warp::reject::Reject for InvalidPayload {}
+
 #[tokio::main]
 async fn main() {
     env_logger::init();
 
     info!("Starting server...");
 
+    let webhook_secret = env::var("WEBHOOK_SECRET").expect("WEBHOOK_SECRET must be set");
+    let with_secret = warp::any().map(move || webhook_secret.clone());
+
+    let status_actions = Config::load_from_env()
+        .unwrap_or_else(|e| panic!("failed to load status-actions config: {}", e));
+    let status_actions = Arc::new(status_actions);
+    let with_config = warp::any().map(move || status_actions.clone());
+
+    let db_pool = feed::init_pool().await;
+    let with_pool = warp::any().map(move || db_pool.clone());
+
     let webhook = warp::path!("webhook")
         .and(warp::post())
-        .and(warp::body::json::<serde_json::Value>())
+        .and(warp::header::optional::<String>("X-Hub-Signature-256"))
+        .and(warp::body::bytes())
+        .and(with_secret)
+        .and_then(verify_signature)
+        .and(with_config)
+        .and(with_pool.clone())
         .and_then(handle_webhook);
 
-    let routes = webhook;
+    let feed_route = warp::path!("feed.atom")
+        .and(warp::get())
+        .and(with_pool)
+        .and_then(serve_feed);
+
+    // `recover` must wrap the whole union, not just `webhook`: applied to
+    // `webhook` alone it makes that filter infallible, so `.or(feed_route)`
+    // never gets a chance to try the right-hand side and a legitimate
+    // `GET /feed.atom` request never reaches `feed_route` at all.
+    let routes = webhook.or(feed_route).recover(handle_rejection);
 
     warp::serve(routes).run(([0, 0, 0, 0], 3030)).await;
 }
 
-async fn prepare_graphql_query(node_id: &str) -> String {
-    let query_template = r#"
-    query {
-        node(id: "$nodeId") {
-            ... on ProjectV2Item {
-                id
-                fieldValues(first: 8) {
-                    nodes {
-                        ... on ProjectV2ItemFieldTextValue {
-                            text
-                            field {
-                                ... on ProjectV2FieldCommon {
-                                    name
-                                }
+async fn serve_feed(pool: SqlitePool) -> Result<impl warp::Reply, Infallible> {
+    let body = feed::render_feed(&pool).await;
+    Ok(warp::reply::with_header(
+        body,
+        "Content-Type",
+        "application/atom+xml; charset=utf-8",
+    ))
+}
+
+/// Checks a `sha256=<hex>` signature header against `HMAC-SHA256(secret,
+/// body)` in constant time. Split out from `verify_signature` so the
+/// comparison itself is testable without a warp request.
+fn signature_matches(secret: &[u8], body: &[u8], header: &str) -> bool {
+    let Some(hex_sig) = header.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(hex_sig) else {
+        return false;
+    };
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret) else {
+        return false;
+    };
+
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Checks the raw webhook body against `X-Hub-Signature-256` before any JSON
+/// parsing happens, so we never act on a payload we can't attribute to GitHub.
+async fn verify_signature(
+    signature: Option<String>,
+    body: warp::hyper::body::Bytes,
+    webhook_secret: String,
+) -> Result<Value, warp::Rejection> {
+    let signature = signature.ok_or_else(|| warp::reject::custom(InvalidSignature))?;
+
+    if !signature_matches(webhook_secret.as_bytes(), &body, &signature) {
+        return Err(warp::reject::custom(InvalidSignature));
+    }
+
+    serde_json::from_slice(&body).map_err(|_| warp::reject::custom(InvalidPayload))
+}
+
+async fn handle_rejection(err: warp::Rejection) -> Result<impl warp::Reply, Infallible> {
+    if err.find::<InvalidSignature>().is_some() {
+        Ok(warp::reply::with_status(
+            "Invalid webhook signature",
+            StatusCode::UNAUTHORIZED,
+        ))
+    } else if err.find::<InvalidPayload>().is_some() {
+        Ok(warp::reply::with_status(
+            "Malformed webhook payload",
+            StatusCode::BAD_REQUEST,
+        ))
+    } else if err.is_not_found() {
+        Ok(warp::reply::with_status("Not found", StatusCode::NOT_FOUND))
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        Ok(warp::reply::with_status(
+            "Method not allowed",
+            StatusCode::METHOD_NOT_ALLOWED,
+        ))
+    } else {
+        Ok(warp::reply::with_status(
+            "Internal error",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ))
+    }
+}
+
+const PROJECT_ITEM_QUERY: &str = r#"
+query($nodeId: ID!) {
+    node(id: $nodeId) {
+        ... on ProjectV2Item {
+            id
+            fieldValues(first: 8) {
+                nodes {
+                    ... on ProjectV2ItemFieldTextValue {
+                        text
+                        field {
+                            ... on ProjectV2FieldCommon {
+                                name
                             }
                         }
-                        ... on ProjectV2ItemFieldDateValue {
-                            date
-                            field {
-                                ... on ProjectV2FieldCommon {
-                                    name
-                                }
+                    }
+                    ... on ProjectV2ItemFieldDateValue {
+                        date
+                        field {
+                            ... on ProjectV2FieldCommon {
+                                name
                             }
                         }
-                        ... on ProjectV2ItemFieldSingleSelectValue {
-                            name
-                            field {
-                                ... on ProjectV2FieldCommon {
-                                    name
-                                }
+                    }
+                    ... on ProjectV2ItemFieldSingleSelectValue {
+                        name
+                        field {
+                            ... on ProjectV2FieldCommon {
+                                name
                             }
                         }
                     }
                 }
-                content {
-                    ... on Issue {
-                        id
-                        title
-                        repository {
-                            name
-                            owner {
-                                login
-                            }
+            }
+            content {
+                ... on Issue {
+                    id
+                    title
+                    repository {
+                        name
+                        owner {
+                            login
                         }
-                        assignees(first: 10) {
-                            nodes {
-                                login
-                            }
+                    }
+                    assignees(first: 10) {
+                        nodes {
+                            login
                         }
                     }
-                    ... on PullRequest {
-                        id
-                        title
-                        assignees(first: 10) {
-                            nodes {
-                                login
-                            }
+                }
+                ... on PullRequest {
+                    id
+                    title
+                    assignees(first: 10) {
+                        nodes {
+                            login
                         }
                     }
                 }
             }
         }
     }
-    "#;
-    query_template.replace("$nodeId", node_id)
 }
+"#;
 
-async fn prepare_issue_number_query(issue_id: &str) -> String {
-    let query_template = r#"
-    query {
-        node(id: "$issueId") {
-            ... on Issue {
-                number
-            }
+const ISSUE_NUMBER_QUERY: &str = r#"
+query($issueId: ID!) {
+    node(id: $issueId) {
+        ... on Issue {
+            number
+        }
+    }
+}
+"#;
+
+const REPOSITORY_ID_QUERY: &str = r#"
+query($owner: String!, $repo: String!) {
+    repository(owner: $owner, name: $repo) {
+        id
+    }
+}
+"#;
+
+const TRANSFER_ISSUE_MUTATION: &str = r#"
+mutation($issueId: ID!, $repoId: ID!) {
+    transferIssue(input: { issueId: $issueId, repositoryId: $repoId }) {
+        issue {
+            number
         }
     }
-    "#;
-    query_template.replace("$issueId", issue_id)
+}
+"#;
+
+fn prepare_graphql_query(node_id: &str) -> (&'static str, Value) {
+    (PROJECT_ITEM_QUERY, serde_json::json!({ "nodeId": node_id }))
+}
+
+fn prepare_issue_number_query(issue_id: &str) -> (&'static str, Value) {
+    (ISSUE_NUMBER_QUERY, serde_json::json!({ "issueId": issue_id }))
+}
+
+fn prepare_repository_id_query(owner: &str, repo: &str) -> (&'static str, Value) {
+    (
+        REPOSITORY_ID_QUERY,
+        serde_json::json!({ "owner": owner, "repo": repo }),
+    )
+}
+
+fn prepare_transfer_issue_mutation(issue_id: &str, repo_id: &str) -> (&'static str, Value) {
+    (
+        TRANSFER_ISSUE_MUTATION,
+        serde_json::json!({ "issueId": issue_id, "repoId": repo_id }),
+    )
 }
 
-async fn send_graphql_request(query: &str, github_token: String) -> Result<Value, reqwest::Error> {
+async fn send_graphql_request(
+    query: &str,
+    variables: Value,
+    github_token: String,
+) -> Result<Value, reqwest::Error> {
     let client = reqwest::Client::new();
-    let query_object = serde_json::json!({ "query": query });
+    let query_object = serde_json::json!({ "query": query, "variables": variables });
 
     let response = client
         .post("https://api.github.com/graphql")
@@ -120,117 +278,432 @@ async fn send_graphql_request(query: &str, github_token: String) -> Result<Value
     Ok(json_response)
 }
 
-async fn handle_webhook(payload: Value) -> Result<impl warp::Reply, warp::Rejection> {
+/// Flattens the `fieldValues.nodes` of a `ProjectV2Item` query response into
+/// a `field name -> value` map, so callers look a field up once instead of
+/// re-scanning the array per check.
+fn field_value_map(json: &Value) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+
+    if let Some(field_values) = json["data"]["node"]["fieldValues"]["nodes"].as_array() {
+        for field_value in field_values {
+            let name = match field_value["field"]["name"].as_str() {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let value = field_value["name"]
+                .as_str()
+                .or_else(|| field_value["text"].as_str())
+                .or_else(|| field_value["date"].as_str());
+
+            if let Some(value) = value {
+                values.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+
+    values
+}
+
+/// Dispatches the octocrab call that corresponds to a status-action rule.
+async fn apply_action(
+    action: &Action,
+    octocrab: &octocrab::Octocrab,
+    repo_owner: &str,
+    repo_name: &str,
+    issue_number: u64,
+) {
+    let issues = octocrab.issues(repo_owner, repo_name);
+
+    let result = match action {
+        Action::Close => issues
+            .update(issue_number)
+            .state(models::IssueState::Closed)
+            .send()
+            .await
+            .map(|_| ()),
+        Action::Reopen => issues
+            .update(issue_number)
+            .state(models::IssueState::Open)
+            .send()
+            .await
+            .map(|_| ()),
+        Action::AddLabel { label } => issues
+            .add_labels(issue_number, &[label.clone()])
+            .await
+            .map(|_| ()),
+        Action::AssignReviewer { reviewer } => issues
+            .add_assignees(issue_number, &[reviewer.as_str()])
+            .await
+            .map(|_| ()),
+    };
+
+    if let Err(e) = result {
+        error!("Failed to apply action {:?} to issue #{}: {}", action, issue_number, e);
+    }
+}
+
+/// Transfers the issue identified by `issue_id` to `target_repo` (given as
+/// `"owner/repo"`) via GitHub's `transferIssue` mutation. Returns the issue's
+/// new number in `target_repo` on success.
+async fn transfer_issue(issue_id: &str, target_repo: &str, github_token: String) -> Option<u64> {
+    let Some((owner, repo)) = target_repo.split_once('/') else {
+        error!("Target Repo {:?} is not in owner/repo form.", target_repo);
+        return None;
+    };
+
+    let (repo_id_query, repo_id_variables) = prepare_repository_id_query(owner, repo);
+    let repo_id_json =
+        match send_graphql_request(repo_id_query, repo_id_variables, github_token.clone()).await {
+            Ok(json) => json,
+            Err(e) => {
+                error!("Failed to resolve node ID for {}: {}", target_repo, e);
+                return None;
+            }
+        };
+
+    let Some(repo_id) = repo_id_json["data"]["repository"]["id"].as_str() else {
+        error!("Repository {} not found or has no node ID.", target_repo);
+        return None;
+    };
+
+    let (mutation, variables) = prepare_transfer_issue_mutation(issue_id, repo_id);
+    match send_graphql_request(mutation, variables, github_token).await {
+        Ok(json) => match json["data"]["transferIssue"]["issue"]["number"].as_u64() {
+            Some(number) => {
+                info!("Transferred issue to {} as #{}.", target_repo, number);
+                Some(number)
+            }
+            None => {
+                error!("transferIssue response had no issue number: {:?}", json);
+                None
+            }
+        },
+        Err(e) => {
+            error!("Failed to transfer issue to {}: {}", target_repo, e);
+            None
+        }
+    }
+}
+
+async fn handle_webhook(
+    payload: Value,
+    status_actions: Arc<Config>,
+    pool: SqlitePool,
+) -> Result<impl warp::Reply, warp::Rejection> {
     info!("Received a webhook call with payload: {:?}", payload);
     let github_token = env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN must be set");
 
-    if let Some(action) = payload.get("action") {
-        if action == "reordered" {
-            info!("It's a reordered event.");
-
-            if let Some(node_id) = payload
-                .get("projects_v2_item")
-                .and_then(|item| item.get("node_id"))
-            {
-                let node_id_str = node_id.as_str().unwrap_or_default();
-                let query = prepare_graphql_query(node_id_str).await;
-
-                match send_graphql_request(&query, github_token.clone()).await {
-                    Ok(json) => {
-                        info!("Received GraphQL response: {:?}", json);
-
-                        let mut is_done = false;
-                        if let Some(field_values) =
-                            json["data"]["node"]["fieldValues"]["nodes"].as_array()
-                        {
-                            for field_value in field_values {
-                                if field_value["field"]["name"].as_str() == Some("Status")
-                                    && field_value["name"].as_str() == Some("Done")
+    let event = match GithubEvent::parse(&payload) {
+        Ok(event) => event,
+        Err(e) => {
+            error!("Failed to parse webhook payload: {}", e);
+            return Ok("Webhook received");
+        }
+    };
+
+    match event {
+        GithubEvent::ProjectsV2Item {
+            action: ProjectsV2ItemAction::Reordered | ProjectsV2ItemAction::Edited,
+            node_id,
+            changed_field,
+        } => {
+            // An `edited` event that names a field we don't act on (e.g. an
+            // assignee or description change) can't possibly affect Status or
+            // Target Repo, so skip the GraphQL round-trip entirely.
+            if let Some(field) = &changed_field {
+                if field != "Status" && field != "Target Repo" {
+                    info!(
+                        "Card {} was edited but only its {:?} field changed; ignoring.",
+                        node_id, field
+                    );
+                    return Ok("Webhook received");
+                }
+            }
+
+            let (query, variables) = prepare_graphql_query(&node_id);
+
+            match send_graphql_request(query, variables, github_token.clone()).await {
+                Ok(json) => {
+                    info!("Received GraphQL response: {:?}", json);
+
+                    let field_values = field_value_map(&json);
+                    let status = field_values.get("Status").map(String::as_str);
+                    let target_repo = field_values.get("Target Repo").map(String::as_str);
+                    let action = status.and_then(|status| status_actions.action_for(status));
+
+                    let current_repo = match (
+                        json["data"]["node"]["content"]["repository"]["owner"]["login"].as_str(),
+                        json["data"]["node"]["content"]["repository"]["name"].as_str(),
+                    ) {
+                        (Some(owner), Some(name)) => Some(format!("{}/{}", owner, name)),
+                        _ => None,
+                    };
+
+                    // Record what we observed this time, regardless of whether a rule
+                    // matched it, so a later `Done -> In Progress -> Done` cycle is seen
+                    // as two real transitions rather than being masked by the status we
+                    // last *acted on*.
+                    let previous_status = feed::last_known_status(&pool, &node_id).await;
+                    let status_changed = previous_status.as_deref() != status;
+                    if let Some(status) = status {
+                        feed::set_known_status(&pool, &node_id, status).await;
+                    }
+
+                    // A Target Repo transfer takes precedence over any matching Status→action
+                    // rule, but is idempotent on the issue's current repository rather than the
+                    // triggering event type: GitHub re-emits `edited` on every card property
+                    // change, so retrying once the issue is already in Target Repo would just
+                    // error against an issue that isn't there anymore.
+                    let transfer_target =
+                        target_repo.filter(|target| current_repo.as_deref() != Some(target));
+
+                    if let Some(target_repo) = transfer_target {
+                        info!("Target Repo is {}, transferring issue.", target_repo);
+
+                        if let Some(issue_id) = json["data"]["node"]["content"]["id"].as_str() {
+                            if let Some(new_issue_number) =
+                                transfer_issue(issue_id, target_repo, github_token.clone()).await
+                            {
+                                let title = json["data"]["node"]["content"]["title"]
+                                    .as_str()
+                                    .unwrap_or_default();
+
+                                if let Some((repo_owner, repo_name)) = target_repo.split_once('/')
                                 {
-                                    is_done = true;
-                                    break;
+                                    feed::record_event(
+                                        &pool,
+                                        &NewCardEvent {
+                                            repo_owner: repo_owner.to_string(),
+                                            repo_name: repo_name.to_string(),
+                                            issue_number: new_issue_number as i64,
+                                            issue_title: title.to_string(),
+                                            status: "transferred".to_string(),
+                                        },
+                                    )
+                                    .await;
                                 }
                             }
+                        } else {
+                            info!("Issue ID not found in the first query.");
+                        }
+                    } else if let Some(action) = action {
+                        // `reordered` carries no information about what changed, and an
+                        // `edited` naming the Status field still fires on a re-delivery of
+                        // the same value, so gate on the status actually having moved
+                        // rather than trusting the event type alone.
+                        if !status_changed {
+                            info!(
+                                "Status for {} is still {:?}; skipping re-applied action.",
+                                node_id, status
+                            );
+                            return Ok("Webhook received");
                         }
 
-                        if is_done {
-                            info!("Status is Done.");
+                        info!("Status is {:?}, applying {:?}.", status, action);
 
-                            if let Some(issue_id) = json["data"]["node"]["content"]["id"].as_str() {
-                                let issue_number_query = prepare_issue_number_query(issue_id).await;
+                        if let Some(issue_id) = json["data"]["node"]["content"]["id"].as_str() {
+                            let (issue_number_query, issue_number_variables) =
+                                prepare_issue_number_query(issue_id);
 
-                                match send_graphql_request(
-                                    &issue_number_query,
-                                    github_token.clone(),
-                                )
-                                .await
-                                {
-                                    Ok(issue_json) => {
-                                        if let Some(issue_number) =
-                                            issue_json["data"]["node"]["number"].as_u64()
-                                        {
-                                            info!("The issue number that this card is representing is: {}", issue_number);
+                            match send_graphql_request(
+                                issue_number_query,
+                                issue_number_variables,
+                                github_token.clone(),
+                            )
+                            .await
+                            {
+                                Ok(issue_json) => {
+                                    if let Some(issue_number) =
+                                        issue_json["data"]["node"]["number"].as_u64()
+                                    {
+                                        info!("The issue number that this card is representing is: {}", issue_number);
 
-                                            if let Some(repo_name) = json["data"]["node"]["content"]
-                                                ["repository"]["name"]
+                                        if let Some(repo_name) = json["data"]["node"]["content"]
+                                            ["repository"]["name"]
+                                            .as_str()
+                                        {
+                                            if let Some(repo_owner) = json["data"]["node"]
+                                                ["content"]["repository"]["owner"]["login"]
                                                 .as_str()
                                             {
-                                                if let Some(repo_owner) = json["data"]["node"]
-                                                    ["content"]["repository"]["owner"]["login"]
+                                                info!(
+                                                    "The issue is in repository: {}/{}",
+                                                    repo_owner, repo_name
+                                                );
+
+                                                let octocrab = octocrab::Octocrab::builder()
+                                                    .personal_token(github_token)
+                                                    .build()
+                                                    .unwrap();
+
+                                                apply_action(
+                                                    action,
+                                                    &octocrab,
+                                                    repo_owner,
+                                                    repo_name,
+                                                    issue_number,
+                                                )
+                                                .await;
+
+                                                let title = json["data"]["node"]["content"]
+                                                    ["title"]
                                                     .as_str()
-                                                {
-                                                    info!(
-                                                        "The issue is in repository: {}/{}",
-                                                        repo_owner, repo_name
-                                                    );
-
-                                                    let octocrab = octocrab::Octocrab::builder()
-                                                        .personal_token(github_token)
-                                                        .build()
-                                                        .unwrap();
-
-                                                    let _ = octocrab
-                                                        .issues(repo_owner, repo_name)
-                                                        .update(issue_number)
-                                                        .state(models::IssueState::Closed)
-                                                        // Send the request
-                                                        .send()
-                                                        .await
-                                                        .unwrap();
-                                                } else {
-                                                    info!("Repository owner not found.");
-                                                }
+                                                    .unwrap_or_default();
+
+                                                feed::record_event(
+                                                    &pool,
+                                                    &NewCardEvent {
+                                                        repo_owner: repo_owner.to_string(),
+                                                        repo_name: repo_name.to_string(),
+                                                        issue_number: issue_number as i64,
+                                                        issue_title: title.to_string(),
+                                                        status: status
+                                                            .unwrap_or_default()
+                                                            .to_string(),
+                                                    },
+                                                )
+                                                .await;
                                             } else {
-                                                info!("Repository information not found.");
+                                                info!("Repository owner not found.");
                                             }
                                         } else {
-                                            info!("Issue number not found in the second query.");
+                                            info!("Repository information not found.");
                                         }
-                                    }
-                                    Err(e) => {
-                                        error!("Failed to make the second GraphQL request: {}", e);
+                                    } else {
+                                        info!("Issue number not found in the second query.");
                                     }
                                 }
-                            } else {
-                                info!("Issue ID not found in the first query.");
+                                Err(e) => {
+                                    error!("Failed to make the second GraphQL request: {}", e);
+                                }
                             }
                         } else {
-                            info!("Status is not Done. Ignoring.");
+                            info!("Issue ID not found in the first query.");
                         }
-                    }
-                    Err(e) => {
-                        error!("Failed to make GraphQL request: {}", e);
+                    } else if let Some(target_repo) = target_repo {
+                        info!(
+                            "Issue is already in Target Repo {}; skipping transfer.",
+                            target_repo
+                        );
+                    } else {
+                        info!("Status {:?} has no matching rule. Ignoring.", status);
                     }
                 }
-            } else {
-                info!("Node ID not found in payload.");
+                Err(e) => {
+                    error!("Failed to make GraphQL request: {}", e);
+                }
             }
-        } else {
-            info!("Not a reordered event. Ignoring.");
         }
-    } else {
-        info!("Action field not found in payload.");
+        GithubEvent::ProjectsV2Item {
+            action: ProjectsV2ItemAction::Archived,
+            node_id,
+            ..
+        } => {
+            info!("Card {} was archived; nothing to do.", node_id);
+        }
+        GithubEvent::ProjectsV2Item {
+            action: ProjectsV2ItemAction::Other(other),
+            node_id,
+            ..
+        } => {
+            info!("Unhandled projects_v2_item action {:?} for {}.", other, node_id);
+        }
+        GithubEvent::Other => {
+            info!("Not a projects_v2_item event. Ignoring.");
+        }
     }
 
     Ok("Webhook received")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[tokio::test]
+    async fn missing_signature_header_is_rejected() {
+        let body = warp::hyper::body::Bytes::from_static(b"{}");
+
+        let result = verify_signature(None, body, "secret".to_string()).await;
+
+        assert!(result.unwrap_err().find::<InvalidSignature>().is_some());
+    }
+
+    #[tokio::test]
+    async fn wrong_signature_is_rejected() {
+        let body = warp::hyper::body::Bytes::from_static(b"{}");
+        let wrong_signature = sign("a-different-secret", &body);
+
+        let result = verify_signature(Some(wrong_signature), body, "secret".to_string()).await;
+
+        assert!(result.unwrap_err().find::<InvalidSignature>().is_some());
+    }
+
+    #[tokio::test]
+    async fn valid_signature_parses_the_payload() {
+        let body = warp::hyper::body::Bytes::from_static(br#"{"action":"reordered"}"#);
+        let signature = sign("secret", &body);
+
+        let result = verify_signature(Some(signature), body, "secret".to_string()).await;
+
+        assert_eq!(result.unwrap(), serde_json::json!({ "action": "reordered" }));
+    }
+
+    #[tokio::test]
+    async fn route_miss_is_not_found_not_internal_error() {
+        let routes = warp::path!("webhook")
+            .and(warp::post())
+            .map(|| "webhook")
+            .or(warp::path!("feed.atom").and(warp::get()).map(|| "feed"))
+            .recover(handle_rejection);
+
+        let resp = warp::test::request()
+            .method("GET")
+            .path("/nope")
+            .reply(&routes)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn wrong_method_is_method_not_allowed_not_internal_error() {
+        let routes = warp::path!("webhook")
+            .and(warp::post())
+            .map(|| "webhook")
+            .or(warp::path!("feed.atom").and(warp::get()).map(|| "feed"))
+            .recover(handle_rejection);
+
+        let resp = warp::test::request()
+            .method("GET")
+            .path("/webhook")
+            .reply(&routes)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn feed_route_is_reachable_through_the_union() {
+        let routes = warp::path!("webhook")
+            .and(warp::post())
+            .map(|| "webhook")
+            .or(warp::path!("feed.atom").and(warp::get()).map(|| "feed"))
+            .recover(handle_rejection);
+
+        let resp = warp::test::request()
+            .method("GET")
+            .path("/feed.atom")
+            .reply(&routes)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}